@@ -0,0 +1,559 @@
+//! Deserialize a [`BmlNode`] tree into a typed value, mirroring `toml_edit`'s `de` module.
+//!
+//! Child nodes map to struct fields by name (attributes are transparent children as well),
+//! repeated names map to `Vec<T>` since [`BmlNode::named()`] preserves their order, and
+//! [`BmlNode::value()`]/[`BmlNode::lines()`] map to scalar fields.
+//!
+//! A single occurrence of a repeated name still goes through the same `Vec<T>` machinery as
+//! multiple occurrences, so `proxy: Vec<Proxy>` deserializes correctly whether `proxy` appears
+//! once or many times.
+
+use core::fmt;
+use std::collections::HashSet;
+
+use serde::de::{
+	self, DeserializeOwned, DeserializeSeed, Deserializer as _, EnumAccess, Error as _,
+	IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+
+use crate::{BmlError, BmlNode};
+
+/// Deserializes `bml` into a value of type `T`.
+///
+/// # Errors
+///
+/// Returns [`BmlError::Parse`] if `bml` is not valid BML and [`BmlError::Serde`] if it does not
+/// match the shape of `T`.
+pub fn from_str<T: DeserializeOwned>(bml: &str) -> Result<T, BmlError> {
+	let root = BmlNode::try_from(bml)?;
+	T::deserialize(Deserializer(&root)).map_err(|Error(message)| BmlError::Serde(message))
+}
+
+#[derive(Debug)]
+struct Error(std::string::String);
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+	fn custom<T: fmt::Display>(message: T) -> Self {
+		Self(message.to_string())
+	}
+}
+
+/// Parses [`BmlNode::value()`] as a number, dispatching to the matching `Visitor::visit_*`.
+macro_rules! deserialize_number {
+	($($method:ident -> $visit:ident: $ty:ty),* $(,)?) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+				let value = self.0.value();
+				match value.parse::<$ty>() {
+					Ok(value) => visitor.$visit(value),
+					Err(_) => Err(Error::custom(format!(
+						"`{value}` is not a valid {}",
+						stringify!($ty)
+					))),
+				}
+			}
+		)*
+	};
+}
+
+/// Deserializer walking a single [`BmlNode`], used for both scalar and nested struct fields.
+struct Deserializer<'de>(&'de BmlNode);
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		if self.0.nodes().len() > 0 {
+			self.deserialize_map(visitor)
+		} else if self.0.lines().next().is_some() {
+			visitor.visit_borrowed_str(self.0.value())
+		} else {
+			visitor.visit_unit()
+		}
+	}
+
+	fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self.0.value() {
+			"true" => visitor.visit_bool(true),
+			"false" => visitor.visit_bool(false),
+			value => Err(Error::custom(format!("`{value}` is not a BML bool"))),
+		}
+	}
+
+	deserialize_number! {
+		deserialize_i8 -> visit_i8: i8,
+		deserialize_i16 -> visit_i16: i16,
+		deserialize_i32 -> visit_i32: i32,
+		deserialize_i64 -> visit_i64: i64,
+		deserialize_i128 -> visit_i128: i128,
+		deserialize_u8 -> visit_u8: u8,
+		deserialize_u16 -> visit_u16: u16,
+		deserialize_u32 -> visit_u32: u32,
+		deserialize_u64 -> visit_u64: u64,
+		deserialize_u128 -> visit_u128: u128,
+		deserialize_f32 -> visit_f32: f32,
+		deserialize_f64 -> visit_f64: f64,
+	}
+
+	fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_borrowed_str(self.0.value())
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_seq(LinesSeqAccess(self.0.lines()))
+	}
+
+	fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_map(NodeMapAccess::new(self.0))
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_map(visitor)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		if self.0.lines().next().is_some() {
+			// Content-based: the scalar value names a unit variant, e.g. `authentication: plain`.
+			visitor.visit_enum(self.0.value().into_deserializer())
+		} else {
+			// Name-based: the single child node's name selects the variant, its body is the
+			// variant's payload.
+			let (name, node) = self
+				.0
+				.nodes()
+				.next()
+				.ok_or_else(|| Error::custom("empty node cannot select an enum variant"))?;
+			visitor.visit_enum(NodeEnumAccess { name, node })
+		}
+	}
+
+	fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.deserialize_str(visitor)
+	}
+
+	serde::forward_to_deserialize_any! {
+		char string bytes byte_buf unit unit_struct newtype_struct tuple tuple_struct ignored_any
+	}
+}
+
+struct NodeEnumAccess<'de> {
+	name: &'de str,
+	node: &'de BmlNode,
+}
+
+impl<'de> EnumAccess<'de> for NodeEnumAccess<'de> {
+	type Error = Error;
+	type Variant = Deserializer<'de>;
+
+	fn variant_seed<V: DeserializeSeed<'de>>(
+		self,
+		seed: V,
+	) -> Result<(V::Value, Self::Variant), Error> {
+		let value = seed.deserialize(self.name.into_deserializer())?;
+		Ok((value, Deserializer(self.node)))
+	}
+}
+
+impl<'de> VariantAccess<'de> for Deserializer<'de> {
+	type Error = Error;
+
+	fn unit_variant(self) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+		seed.deserialize(self)
+	}
+
+	fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn struct_variant<V: Visitor<'de>>(
+		self,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_struct("", fields, visitor)
+	}
+}
+
+/// Sequence access over a node's data lines, used for `Vec<String>`-like leaf fields.
+struct LinesSeqAccess<'de, I: Iterator<Item = &'de str>>(I);
+
+impl<'de, I: Iterator<Item = &'de str>> SeqAccess<'de> for LinesSeqAccess<'de, I> {
+	type Error = Error;
+
+	fn next_element_seed<T: DeserializeSeed<'de>>(
+		&mut self,
+		seed: T,
+	) -> Result<Option<T::Value>, Error> {
+		self.0
+			.next()
+			.map(|line| seed.deserialize(line.into_deserializer()))
+			.transpose()
+	}
+}
+
+/// Map access over a node's children, grouping repeated names into one sequence value.
+struct NodeMapAccess<'de> {
+	node: &'de BmlNode,
+	names: std::vec::IntoIter<&'de str>,
+	current: &'de str,
+}
+
+impl<'de> NodeMapAccess<'de> {
+	fn new(node: &'de BmlNode) -> Self {
+		let mut seen = HashSet::new();
+		let names = node
+			.nodes()
+			.map(|(name, _)| name)
+			.filter(|name| seen.insert(*name))
+			.collect::<std::vec::Vec<_>>()
+			.into_iter();
+		Self {
+			node,
+			names,
+			current: "",
+		}
+	}
+}
+
+impl<'de> MapAccess<'de> for NodeMapAccess<'de> {
+	type Error = Error;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+		match self.names.next() {
+			Some(name) => {
+				self.current = name;
+				seed.deserialize(name.into_deserializer()).map(Some)
+			}
+			None => Ok(None),
+		}
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+		// Always go through `NodesSeqDeserializer`, even for a single occurrence: a bare
+		// `Deserializer` would instead expose that one node's own data lines as a sequence,
+		// silently dropping the node itself when the target type is `Vec<T>`.
+		seed.deserialize(NodesSeqDeserializer(self.node.named(self.current)))
+	}
+}
+
+/// Deserializer over all occurrences of a repeated name.
+///
+/// A sequence request (`Vec<T>`) iterates the nodes themselves, except when there is exactly one
+/// occurrence and it carries no children of its own: then it is a leaf like `description`, and its
+/// *data lines* become the sequence instead, so `Vec<String>` still works for multi-line data.
+/// Any other request (a scalar, struct, map or enum field with a single occurrence) delegates to
+/// that first node directly.
+struct NodesSeqDeserializer<I>(I);
+
+impl<'de, I: Iterator<Item = &'de BmlNode> + ExactSizeIterator> NodesSeqDeserializer<I> {
+	fn first(mut self) -> Deserializer<'de> {
+		Deserializer(self.0.next().expect("caller only builds this for a non-empty match"))
+	}
+}
+
+/// Forwards a non-seq deserialize method to this occurrence's first (and, by convention, only
+/// meaningful) node.
+macro_rules! forward_to_first {
+	($($method:ident),* $(,)?) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+				self.first().$method(visitor)
+			}
+		)*
+	};
+}
+
+impl<'de, I: Iterator<Item = &'de BmlNode> + ExactSizeIterator> de::Deserializer<'de>
+	for NodesSeqDeserializer<I>
+{
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_any(visitor)
+	}
+
+	fn deserialize_seq<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Error> {
+		if self.0.len() == 1 {
+			let node = self.0.next().expect("length checked above");
+			if node.nodes().len() == 0 {
+				return visitor.visit_seq(LinesSeqAccess(node.lines()));
+			}
+			return visitor.visit_seq(NodesSeqAccess(core::iter::once(node)));
+		}
+		visitor.visit_seq(NodesSeqAccess(self.0))
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_bool(visitor)
+	}
+
+	forward_to_first! {
+		deserialize_i8,
+		deserialize_i16,
+		deserialize_i32,
+		deserialize_i64,
+		deserialize_i128,
+		deserialize_u8,
+		deserialize_u16,
+		deserialize_u32,
+		deserialize_u64,
+		deserialize_u128,
+		deserialize_f32,
+		deserialize_f64,
+	}
+
+	fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_char(visitor)
+	}
+
+	fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_str(visitor)
+	}
+
+	fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_string(visitor)
+	}
+
+	fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_bytes(visitor)
+	}
+
+	fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_byte_buf(visitor)
+	}
+
+	fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_unit(visitor)
+	}
+
+	fn deserialize_unit_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.first().deserialize_unit_struct(name, visitor)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.first().deserialize_newtype_struct(name, visitor)
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_tuple(len, visitor)
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		len: usize,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.first().deserialize_tuple_struct(name, len, visitor)
+	}
+
+	fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_map(visitor)
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.first().deserialize_struct(name, fields, visitor)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.first().deserialize_enum(name, variants, visitor)
+	}
+
+	fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_identifier(visitor)
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.first().deserialize_ignored_any(visitor)
+	}
+}
+
+struct NodesSeqAccess<I>(I);
+
+impl<'de, I: Iterator<Item = &'de BmlNode>> SeqAccess<'de> for NodesSeqAccess<I> {
+	type Error = Error;
+
+	fn next_element_seed<T: DeserializeSeed<'de>>(
+		&mut self,
+		seed: T,
+	) -> Result<Option<T::Value>, Error> {
+		self.0
+			.next()
+			.map(|node| seed.deserialize(Deserializer(node)))
+			.transpose()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde::Deserialize;
+
+	use super::from_str;
+
+	#[derive(Debug, PartialEq, Deserialize)]
+	struct Proxy {
+		host: std::string::String,
+	}
+
+	#[derive(Debug, PartialEq, Deserialize)]
+	#[serde(rename_all = "lowercase")]
+	enum Authentication {
+		Plain,
+		None,
+	}
+
+	#[test]
+	fn scalars_and_description_lines() {
+		#[derive(Debug, PartialEq, Deserialize)]
+		struct Server {
+			port: u16,
+			service: bool,
+			host: std::string::String,
+			description: std::vec::Vec<std::string::String>,
+		}
+		let server: Server = from_str(concat!(
+			"port: 80\n",
+			"service: true\n",
+			"host: example.com\n",
+			"description\n",
+			"  :Primary web-facing server\n",
+			"  :Provides commerce-related functionality\n",
+		))
+		.unwrap();
+		assert_eq!(
+			server,
+			Server {
+				port: 80,
+				service: true,
+				host: "example.com".into(),
+				description: std::vec![
+					"Primary web-facing server".into(),
+					"Provides commerce-related functionality".into(),
+				],
+			}
+		);
+	}
+
+	#[test]
+	fn single_occurrence_of_repeated_name_still_becomes_a_vec() {
+		#[derive(Debug, PartialEq, Deserialize)]
+		struct Server {
+			proxy: std::vec::Vec<Proxy>,
+		}
+		let server: Server = from_str("proxy\n  host: proxy.example.com\n").unwrap();
+		assert_eq!(
+			server,
+			Server {
+				proxy: std::vec![Proxy {
+					host: "proxy.example.com".into()
+				}],
+			}
+		);
+	}
+
+	#[test]
+	fn multiple_occurrences_of_repeated_name_become_a_vec() {
+		#[derive(Debug, PartialEq, Deserialize)]
+		struct Server {
+			proxy: std::vec::Vec<Proxy>,
+		}
+		let server: Server = from_str(concat!(
+			"proxy\n  host: a.example.com\n",
+			"proxy\n  host: b.example.com\n",
+		))
+		.unwrap();
+		assert_eq!(
+			server,
+			Server {
+				proxy: std::vec![
+					Proxy {
+						host: "a.example.com".into()
+					},
+					Proxy {
+						host: "b.example.com".into()
+					},
+				],
+			}
+		);
+	}
+
+	#[test]
+	fn nested_struct_field() {
+		#[derive(Debug, PartialEq, Deserialize)]
+		struct Server {
+			proxy: Proxy,
+		}
+		let server: Server = from_str("proxy\n  host: proxy.example.com\n").unwrap();
+		assert_eq!(
+			server,
+			Server {
+				proxy: Proxy {
+					host: "proxy.example.com".into()
+				},
+			}
+		);
+	}
+
+	#[test]
+	fn content_based_enum_variant() {
+		#[derive(Debug, PartialEq, Deserialize)]
+		struct Proxy {
+			authentication: Authentication,
+		}
+		let proxy: Proxy = from_str("authentication: plain\n").unwrap();
+		assert_eq!(
+			proxy,
+			Proxy {
+				authentication: Authentication::Plain,
+			}
+		);
+	}
+}
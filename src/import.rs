@@ -0,0 +1,301 @@
+//! Resolves `include` directives to compose an ares-style database split across many files,
+//! inspired by `dhall`'s `import.rs` and substitutions.
+//!
+//! A directive is a child node named `include` whose value is a target understood by the
+//! configured [`ImportResolver`]; [`BmlNode::resolve_imports()`] replaces each in-place with the
+//! parsed contents it resolves to, recursing into nested includes. The result is a fully-expanded
+//! standalone [`BmlNode`] that re-serializes with the existing [`Display`](core::fmt::Display).
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ordered_multimap::ListOrderedMultimap;
+
+use crate::{BmlError, BmlName, BmlNode};
+
+const INCLUDE: &str = "include";
+
+/// A [`BmlNode`]'s children, keyed by name.
+type Children = ListOrderedMultimap<BmlName, BmlNode>;
+
+/// Supplies the BML text an `include` directive's target names.
+///
+/// Implement this for non-filesystem sources (embedded assets, archives); the filesystem case is
+/// covered by [`BmlNode::resolve_imports()`]. Required to be [`Clone`] so [`Self::nested()`] can
+/// rebase a resolver without needing to know its concrete fields.
+pub trait ImportResolver: Clone {
+	/// Returns the BML text for `target`, the value of an `include` node.
+	///
+	/// # Errors
+	///
+	/// Returns an error if `target` cannot be read.
+	fn resolve(&self, target: &str) -> Result<std::string::String, std::string::String>;
+	/// Canonical identifier for `target`, used to detect cyclic includes.
+	///
+	/// Defaults to `target` itself; resolvers for which distinct targets may name the same
+	/// underlying source (e.g. relative filesystem paths) should override this.
+	fn canonicalize(&self, target: &str) -> std::string::String {
+		target.into()
+	}
+	/// Returns a resolver for includes found inside `target`, rebased to resolve relative to
+	/// `target`'s own location.
+	///
+	/// Defaults to cloning `self`, i.e. every include shares one location; resolvers whose
+	/// targets may appear in different directories (e.g. relative filesystem paths) should
+	/// override this so a nested `include` is resolved relative to the file that contains it,
+	/// not the original top-level location.
+	fn nested(&self, target: &str) -> Self {
+		let _ = target;
+		self.clone()
+	}
+}
+
+#[derive(Clone)]
+struct FsResolver {
+	base_dir: PathBuf,
+}
+
+impl ImportResolver for FsResolver {
+	fn resolve(&self, target: &str) -> Result<std::string::String, std::string::String> {
+		fs::read_to_string(self.base_dir.join(target)).map_err(|error| error.to_string())
+	}
+
+	fn canonicalize(&self, target: &str) -> std::string::String {
+		let path = self.base_dir.join(target);
+		path.canonicalize()
+			.unwrap_or(path)
+			.display()
+			.to_string()
+	}
+
+	fn nested(&self, target: &str) -> Self {
+		let base_dir = self
+			.base_dir
+			.join(target)
+			.parent()
+			.map_or_else(|| self.base_dir.clone(), Path::to_path_buf);
+		Self { base_dir }
+	}
+}
+
+impl BmlNode {
+	/// Resolves every `include` directive against files relative to `base_dir`.
+	///
+	/// # Errors
+	///
+	/// Returns [`BmlError::Import`] if an include target cannot be read or parsed, and
+	/// [`BmlError::Cycle`] if an `include` chain refers back to itself.
+	pub fn resolve_imports(&mut self, base_dir: &Path) -> Result<(), BmlError> {
+		self.resolve_imports_with(&FsResolver {
+			base_dir: base_dir.into(),
+		})
+	}
+
+	/// Resolves every `include` directive via `resolver`, replacing each with the parsed contents
+	/// it supplies.
+	///
+	/// # Errors
+	///
+	/// Returns [`BmlError::Import`] if an include target cannot be read or parsed, and
+	/// [`BmlError::Cycle`] if an `include` chain refers back to itself.
+	pub fn resolve_imports_with(
+		&mut self,
+		resolver: &impl ImportResolver,
+	) -> Result<(), BmlError> {
+		self.splice_includes(resolver, &mut HashSet::new())
+	}
+
+	fn splice_includes(
+		&mut self,
+		resolver: &impl ImportResolver,
+		visited: &mut HashSet<std::string::String>,
+	) -> Result<(), BmlError> {
+		// Take `self.node` and put it back whether or not splicing succeeds: an early failure on
+		// any one child (a cycle, an unreadable or unparsable include, or a failure deeper in the
+		// tree) must not wipe out unrelated siblings already iterated. Rebuilt by ownership below
+		// instead of cloning upfront, which would be O(depth²) on a long chain; on failure, the
+		// includes already spliced before it stay expanded.
+		let children = std::mem::take(&mut self.node);
+		match Self::splice_children(children, resolver, visited) {
+			Ok(spliced) => {
+				self.node = spliced;
+				Ok(())
+			}
+			Err(restore) => {
+				let (error, unspliced) = *restore;
+				self.node = unspliced;
+				Err(error)
+			}
+		}
+	}
+
+	/// Splices every child in `children`, returning the rebuilt map. On failure, returns the
+	/// error alongside `children` reassembled in their original order: the siblings already
+	/// spliced (with their includes expanded), followed by the child that failed (as returned by
+	/// [`Self::splice_child()`]) and every child not yet reached. Boxed per `clippy::result_large_err`,
+	/// since restoring the whole map on the error path makes it as large as the map itself.
+	fn splice_children(
+		children: Children,
+		resolver: &impl ImportResolver,
+		visited: &mut HashSet<std::string::String>,
+	) -> Result<Children, Box<(BmlError, Children)>> {
+		let mut spliced = ListOrderedMultimap::default();
+		let mut children = children.into_iter();
+		while let Some((name, node)) = children.next() {
+			match Self::splice_child(name, node, resolver, visited) {
+				Ok(spliced_child) => spliced.extend(spliced_child),
+				Err(restore) => {
+					let (error, name, node) = *restore;
+					spliced.append(name, node);
+					spliced.extend(children);
+					return Err(Box::new((error, spliced)));
+				}
+			}
+		}
+		Ok(spliced)
+	}
+
+	/// Splices a single `(name, node)` child, returning the node(s) it expands to: itself, once
+	/// spliced, unless it is an `include`, in which case its resolved contents' own children. On
+	/// failure, returns `name` and `node` back so the caller can restore them: a failed `include`
+	/// comes back untouched, any other child with the includes spliced before the failure. Boxed
+	/// per `clippy::result_large_err`.
+	fn splice_child(
+		name: BmlName,
+		mut node: BmlNode,
+		resolver: &impl ImportResolver,
+		visited: &mut HashSet<std::string::String>,
+	) -> Result<Children, Box<(BmlError, BmlName, BmlNode)>> {
+		if &*name != INCLUDE {
+			return match node.splice_includes(resolver, visited) {
+				Ok(()) => {
+					let mut spliced = ListOrderedMultimap::default();
+					spliced.append(name, node);
+					Ok(spliced)
+				}
+				Err(error) => Err(Box::new((error, name, node))),
+			};
+		}
+		let target = node.value().to_owned();
+		let id = resolver.canonicalize(&target);
+		if !visited.insert(id.clone()) {
+			return Err(Box::new((BmlError::Cycle(target), name, node)));
+		}
+		let bml = match resolver.resolve(&target) {
+			Ok(bml) => bml,
+			Err(error) => {
+				return Err(Box::new((BmlError::Import { path: target, error }, name, node)))
+			}
+		};
+		let mut included = match BmlNode::try_from(bml.as_str()) {
+			Ok(included) => included,
+			Err(error) => {
+				return Err(Box::new((
+					BmlError::Import {
+						path: target,
+						error: error.to_string(),
+					},
+					name,
+					node,
+				)))
+			}
+		};
+		if let Err(error) = included.splice_includes(&resolver.nested(&target), visited) {
+			return Err(Box::new((error, name, node)));
+		}
+		visited.remove(&id);
+		Ok(included.node)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use super::ImportResolver;
+	use crate::{BmlError, BmlNode};
+
+	/// In-memory stand-in for [`super::FsResolver`], keyed on `"dir/target"`-style paths with
+	/// `'/'` separators, so `include` resolution can be exercised without touching the
+	/// filesystem.
+	#[derive(Clone)]
+	struct MapResolver {
+		files: HashMap<std::string::String, std::string::String>,
+		dir: std::string::String,
+	}
+
+	impl MapResolver {
+		fn new(files: &[(&str, &str)]) -> Self {
+			Self {
+				files: files
+					.iter()
+					.map(|&(path, bml)| (path.into(), bml.into()))
+					.collect(),
+				dir: std::string::String::new(),
+			}
+		}
+
+		fn join(&self, target: &str) -> std::string::String {
+			if self.dir.is_empty() {
+				target.into()
+			} else {
+				std::format!("{}/{target}", self.dir)
+			}
+		}
+	}
+
+	impl ImportResolver for MapResolver {
+		fn resolve(&self, target: &str) -> Result<std::string::String, std::string::String> {
+			self.files
+				.get(&self.join(target))
+				.cloned()
+				.ok_or_else(|| std::format!("no such file: {}", self.join(target)))
+		}
+
+		fn canonicalize(&self, target: &str) -> std::string::String {
+			self.join(target)
+		}
+
+		fn nested(&self, target: &str) -> Self {
+			let joined = self.join(target);
+			let dir = joined
+				.rsplit_once('/')
+				.map_or_else(std::string::String::new, |(dir, _)| dir.into());
+			Self {
+				files: self.files.clone(),
+				dir,
+			}
+		}
+	}
+
+	#[test]
+	fn rejects_cyclic_includes() {
+		let resolver = MapResolver::new(&[("a", "include: a\n")]);
+		let mut root = BmlNode::try_from("include: a\n").unwrap();
+		let error = root.resolve_imports_with(&resolver).unwrap_err();
+		assert!(matches!(error, BmlError::Cycle(target) if target == "a"));
+	}
+
+	#[test]
+	fn preserves_unrelated_siblings_when_an_include_fails() {
+		let resolver = MapResolver::new(&[]);
+		let mut root = BmlNode::try_from("host: a\ninclude: missing\nport: 80\n").unwrap();
+		root.resolve_imports_with(&resolver).unwrap_err();
+		assert_eq!(root.named("host").next().unwrap().value(), "a");
+		assert_eq!(root.named("port").next().unwrap().value(), "80");
+		assert_eq!(root.named("include").count(), 1);
+	}
+
+	#[test]
+	fn rebases_nested_includes_relative_to_the_including_file() {
+		let resolver = MapResolver::new(&[
+			("a", "include: sub/b\n"),
+			("sub/b", "include: c\n"),
+			("sub/c", "host: example.com\n"),
+		]);
+		let mut root = BmlNode::try_from("include: a\n").unwrap();
+		root.resolve_imports_with(&resolver).unwrap();
+		assert_eq!(root.named("host").next().unwrap().value(), "example.com");
+	}
+}
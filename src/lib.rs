@@ -74,11 +74,34 @@ pub(crate) mod derive {
 	pub struct BmlParser;
 }
 
-/// BML parser error.
+#[cfg(feature = "serde")]
+pub mod de;
+pub mod import;
+#[cfg(feature = "serde")]
+pub mod ser;
+pub mod visit;
+
+/// BML error.
 #[derive(Debug, PartialEq, Eq, Clone, Error)]
-#[error("Invalid BML\n{}", inner)]
-pub struct BmlError {
-	inner: Error<Rule>,
+pub enum BmlError {
+	/// Invalid BML syntax.
+	#[error("Invalid BML\n{0}")]
+	Parse(Error<Rule>),
+	/// Error (de)serializing a typed value via [`de`] or [`ser`].
+	#[cfg(feature = "serde")]
+	#[error("{0}")]
+	Serde(std::string::String),
+	/// Error resolving an `include` directive via [`import::ImportResolver`].
+	#[error("Failed to import `{path}`: {error}")]
+	Import {
+		/// The `include` target that failed.
+		path: std::string::String,
+		/// What went wrong while reading or parsing it.
+		error: std::string::String,
+	},
+	/// A chain of `include` directives that refers back to itself.
+	#[error("Cyclic include of `{0}`")]
+	Cycle(std::string::String),
 }
 
 type BmlName = String;
@@ -139,6 +162,42 @@ impl fmt::Display for BmlIndent {
 	}
 }
 
+/// Byte range and line/column of a [`BmlNode`] or attribute as parsed by [`BmlNode::try_from_spanned()`].
+///
+/// Ignored by [`BmlNode`]'s [`PartialEq`], which already only compares `data` and `node`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Span {
+	/// Byte offset of the first character.
+	pub start: usize,
+	/// Byte offset one past the last character.
+	pub end: usize,
+	/// One-based line number of the first character.
+	pub line: usize,
+	/// One-based column number of the first character.
+	pub col: usize,
+}
+
+impl From<pest::Span<'_>> for Span {
+	fn from(span: pest::Span) -> Self {
+		let (line, col) = span.start_pos().line_col();
+		Self {
+			start: span.start(),
+			end: span.end(),
+			line,
+			col,
+		}
+	}
+}
+
+/// Whether a [`BmlNode::push_attr()`] value is rendered quoted or unquoted.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Quote {
+	/// Rendered as `name="value"`.
+	Quoted,
+	/// Rendered as `name=value`.
+	Unquoted,
+}
+
 /// BML node comprising data [`Self::lines()`] and child [`Self::nodes()`].
 ///
 /// By design, attributes are considered child nodes as well but carry a flag marking them as
@@ -148,14 +207,17 @@ pub struct BmlNode {
 	kind: BmlKind,
 	data: BmlData,
 	node: ListOrderedMultimap<BmlName, BmlNode>,
+	span: Option<Span>,
 }
 
 impl BmlNode {
 	/// Value comprising data lines with `'\n'` removed from last line.
+	///
+	/// Empty for a node with no data yet, e.g. one freshly built via [`Self::elem()`].
 	#[must_use]
 	#[inline]
 	pub fn value(&self) -> &str {
-		&self.data[..self.data.len() - 1]
+		self.data.strip_suffix('\n').unwrap_or(&self.data)
 	}
 	/// Iterator over data lines.
 	#[must_use]
@@ -163,6 +225,15 @@ impl BmlNode {
 	pub fn lines(&self) -> impl DoubleEndedIterator<Item = &str> {
 		self.data.lines()
 	}
+	/// Byte range and line/column this node was parsed from.
+	///
+	/// Only populated by [`Self::try_from_spanned()`]; plain [`Self::try_from()`] leaves it `None`
+	/// so the common parse path stays lean.
+	#[must_use]
+	#[inline]
+	pub fn span(&self) -> Option<Span> {
+		self.span
+	}
 	/// Iterator over child nodes as `(name, node)` tuples.
 	#[must_use]
 	#[inline]
@@ -180,6 +251,77 @@ impl BmlNode {
 	) -> impl DoubleEndedIterator<Item = &BmlNode> + ExactSizeIterator {
 		self.node.get_all(name)
 	}
+	/// The `index`-th (0-based) child node named `name`.
+	///
+	/// Complexity: *O(index)*
+	#[must_use]
+	#[inline]
+	pub fn nth(&self, name: &str, index: usize) -> Option<&BmlNode> {
+		self.named(name).nth(index)
+	}
+	/// Walks a slash-separated path of names, each optionally indexed as `name[n]` (default `0`),
+	/// returning the selected node.
+	///
+	/// E.g. `"server/proxy[0]/host"` is equivalent to
+	/// `root.named("server").nth(0)?.named("proxy").nth(0)?.named("host").nth(0)?`.
+	#[must_use]
+	pub fn at(&self, path: &str) -> Option<&BmlNode> {
+		let mut node = self;
+		for segment in path.split('/') {
+			let (name, index) = match segment.strip_suffix(']') {
+				Some(rest) => {
+					let (name, index) = rest.split_once('[')?;
+					(name, index.parse().ok()?)
+				}
+				None => (segment, 0),
+			};
+			node = node.nth(name, index)?;
+		}
+		Some(node)
+	}
+	/// Mutable iterator over child nodes as `(name, node)` tuples.
+	#[inline]
+	pub fn get_mut(&mut self) -> impl Iterator<Item = (&str, &mut BmlNode)> {
+		self.node.iter_mut().map(|(name, node)| (name.as_str(), node))
+	}
+	/// Mutable iterator over child nodes of `name`.
+	#[inline]
+	pub fn named_mut(&mut self, name: &str) -> impl Iterator<Item = &mut BmlNode> {
+		self.node.get_all_mut(name)
+	}
+	/// Appends a child element node named `name`.
+	#[inline]
+	pub fn push_node(&mut self, name: &str, node: BmlNode) {
+		self.append((name.into(), node));
+	}
+	/// Appends an attribute named `name` with `value`, rendered per `quote`.
+	#[inline]
+	pub fn push_attr(&mut self, name: &str, value: &str, quote: Quote) {
+		let mut attr = Self::attr();
+		attr.kind = Attr {
+			quote: quote == Quote::Quoted,
+		};
+		attr.data.push_str(value);
+		attr.data.push('\n');
+		self.append((name.into(), attr));
+	}
+	/// Replaces this node's value with a single data line.
+	#[inline]
+	pub fn set_value(&mut self, value: &str) {
+		self.data.clear();
+		self.push_line(value);
+	}
+	/// Appends a line to this node's data.
+	#[inline]
+	pub fn push_line(&mut self, line: &str) {
+		self.data.push_str(line);
+		self.data.push('\n');
+	}
+	/// Removes all child nodes named `name`, returning how many were removed.
+	#[inline]
+	pub fn remove(&mut self, name: &str) -> usize {
+		self.node.remove_all(name).count()
+	}
 	/// Indent `string` of child nodes and level as `repeat` times `string`.
 	///
 	/// Default is two spaces as in `"  "` and no root indent (`0`). Usual alternative is a
@@ -205,9 +347,10 @@ impl BmlNode {
 			..Self::default()
 		}
 	}
+	/// Constructs a blank element node, e.g. to grow a tree via [`Self::push_node()`].
 	#[must_use]
 	#[inline]
-	fn elem() -> Self {
+	pub fn elem() -> Self {
 		Self {
 			kind: Elem,
 			..Self::default()
@@ -239,26 +382,28 @@ impl BmlNode {
 			Elem => {
 				write!(f, "{indent}{name}")?;
 				let indent = indent.next();
+				// Partition by kind rather than assuming attributes form a prefix of `self.node`:
+				// `push_attr`/`push_node` only ever append, so an attribute pushed after an
+				// element child (or one already present from parsing) would otherwise land past
+				// where a prefix scan stops looking.
+				let is_attr = |(_name, node): &(&str, &BmlNode)| matches!(node.kind, Attr { .. });
 				let mut attrs = 0;
-				for (name, attr) in self
-					.nodes()
-					.take_while(|(_name, node)| matches!(node.kind, Attr { .. }))
-				{
+				for (name, attr) in self.nodes().filter(is_attr) {
 					attrs += 1;
 					attr.serialize(f, name, indent)?;
 				}
 				let mut lines = self.lines();
 				let first = lines.next();
 				let second = lines.next();
-				if attrs == 0 && first.is_some() && second.is_none() {
-					writeln!(f, ": {}", first.unwrap())?;
+				if let (0, Some(first), None) = (attrs, first, second) {
+					writeln!(f, ": {first}")?;
 				} else {
 					writeln!(f)?;
 					for line in self.lines() {
 						writeln!(f, "{indent}:{line}")?;
 					}
 				}
-				for (name, elem) in self.nodes().skip(attrs) {
+				for (name, elem) in self.nodes().filter(|entry| !is_attr(entry)) {
 					elem.serialize(f, name, indent)?;
 				}
 			}
@@ -284,13 +429,25 @@ impl PartialEq for BmlNode {
 	}
 }
 
-impl TryFrom<&str> for BmlNode {
-	type Error = BmlError;
+impl BmlNode {
+	/// Parses `bml`, additionally recording each node's and attribute's [`Span`] as [`Self::span()`].
+	///
+	/// Gated behind its own constructor so the default [`Self::try_from()`] stays lean; use this
+	/// when precise diagnostics (e.g. a linter or LSP) need to point at a specific attribute or
+	/// data line instead of reporting "Invalid BML".
+	///
+	/// # Errors
+	///
+	/// Returns [`BmlError::Parse`] if `bml` is not valid BML.
+	pub fn try_from_spanned(bml: &str) -> Result<Self, BmlError> {
+		Self::parse(bml, true)
+	}
 
-	fn try_from(bml: &str) -> Result<Self, Self::Error> {
-		fn parse_node(pair: Pair<Rule>) -> (BmlName, BmlNode) {
+	fn parse(bml: &str, spanned: bool) -> Result<Self, BmlError> {
+		fn parse_node(pair: Pair<Rule>, spanned: bool) -> (BmlName, BmlNode) {
 			let mut name = BmlName::new();
 			let mut node = BmlNode::elem();
+			node.span = spanned.then(|| Span::from(pair.as_span()));
 			for pair in pair.into_inner() {
 				match pair.as_rule() {
 					Rule::name => name = pair.as_str().into(),
@@ -301,10 +458,31 @@ impl TryFrom<&str> for BmlNode {
 					Rule::attr => {
 						let mut name = BmlName::new();
 						let mut attr = BmlNode::attr();
+						// `attr`'s own span includes the leading `indent+` it is silently
+						// prefixed with, unlike `elem`/`line`, whose indent is consumed by the
+						// wrapping rule before `node`'s span starts. Build the span from `name`'s
+						// start through `data`'s end (or `name`'s end, absent `data`) instead, so
+						// it points at the attribute like an elem span points at the element.
+						let mut span = None;
 						for pair in pair.into_inner() {
 							match pair.as_rule() {
-								Rule::name => name = pair.as_str().into(),
+								Rule::name => {
+									let name_span = pair.as_span();
+									span = spanned.then(|| {
+										let (line, col) = name_span.start_pos().line_col();
+										Span {
+											start: name_span.start(),
+											end: name_span.end(),
+											line,
+											col,
+										}
+									});
+									name = pair.as_str().into();
+								}
 								Rule::data => {
+									if let Some(span) = &mut span {
+										span.end = pair.as_span().end();
+									}
 									for data in pair.into_inner() {
 										if data.as_rule() == Rule::space_data_inner {
 											attr.kind = Attr { quote: false }
@@ -316,9 +494,10 @@ impl TryFrom<&str> for BmlNode {
 								_ => unreachable!(),
 							}
 						}
+						attr.span = span;
 						node.append((name, attr));
 					}
-					Rule::node => node.append(parse_node(pair)),
+					Rule::node => node.append(parse_node(pair, spanned)),
 					_ => unreachable!(),
 				}
 			}
@@ -326,9 +505,15 @@ impl TryFrom<&str> for BmlNode {
 		}
 
 		let mut root = BmlNode::root();
-		for pair in BmlParser::parse(Rule::root, bml).map_err(|inner| BmlError { inner })? {
+		root.span = spanned.then(|| Span {
+			start: 0,
+			end: bml.len(),
+			line: 1,
+			col: 1,
+		});
+		for pair in BmlParser::parse(Rule::root, bml).map_err(BmlError::Parse)? {
 			match pair.as_rule() {
-				Rule::node => root.append(parse_node(pair)),
+				Rule::node => root.append(parse_node(pair, spanned)),
 				Rule::EOI => (),
 				_ => unreachable!(),
 			}
@@ -337,6 +522,15 @@ impl TryFrom<&str> for BmlNode {
 	}
 }
 
+impl TryFrom<&str> for BmlNode {
+	type Error = BmlError;
+
+	#[inline]
+	fn try_from(bml: &str) -> Result<Self, Self::Error> {
+		Self::parse(bml, false)
+	}
+}
+
 impl fmt::Display for BmlNode {
 	#[inline]
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -346,7 +540,7 @@ impl fmt::Display for BmlNode {
 
 #[cfg(test)]
 mod tests {
-	use super::BmlNode;
+	use super::{BmlNode, Quote};
 
 	#[test]
 	fn ordered_iteration() {
@@ -358,4 +552,132 @@ mod tests {
 			vec![("0", "a"), ("1", "b"), ("2", "c"), ("1", "d"), ("3", "e")]
 		);
 	}
+
+	#[test]
+	fn value_on_empty_data_does_not_panic() {
+		assert_eq!(BmlNode::elem().value(), "");
+	}
+
+	#[test]
+	fn builds_a_tree_and_round_trips_it_through_display() {
+		let mut root = BmlNode::try_from("").unwrap();
+		let mut proxy = BmlNode::elem();
+		proxy.push_attr("port", "8080", Quote::Quoted);
+		let mut authentication = BmlNode::elem();
+		authentication.set_value("plain");
+		proxy.push_node("authentication", authentication);
+		root.push_node("proxy", proxy);
+		assert_eq!(
+			root.to_string(),
+			"proxy port=\"8080\"\n  authentication: plain\n"
+		);
+	}
+
+	#[test]
+	fn push_attr_unquoted_renders_without_quotes() {
+		let mut root = BmlNode::try_from("").unwrap();
+		let mut proxy = BmlNode::elem();
+		proxy.push_attr("port", "8080", Quote::Unquoted);
+		root.push_node("proxy", proxy);
+		assert_eq!(root.to_string(), "proxy port=8080\n");
+	}
+
+	#[test]
+	fn push_line_builds_multi_line_data() {
+		let mut description = BmlNode::elem();
+		description.push_line("one");
+		description.push_line("two");
+		assert_eq!(description.lines().collect::<Vec<_>>(), vec!["one", "two"]);
+	}
+
+	#[test]
+	fn push_attr_after_push_node_still_serializes_as_an_attribute() {
+		let mut proxy = BmlNode::elem();
+		let mut authentication = BmlNode::elem();
+		authentication.set_value("plain");
+		proxy.push_node("authentication", authentication);
+		proxy.push_attr("port", "8080", Quote::Quoted);
+		let mut root = BmlNode::try_from("").unwrap();
+		root.push_node("proxy", proxy);
+		assert_eq!(
+			root.to_string(),
+			"proxy port=\"8080\"\n  authentication: plain\n"
+		);
+	}
+
+	#[test]
+	fn named_mut_and_remove() {
+		let mut root = BmlNode::try_from("a: 1\na: 2\nb: 3\n").unwrap();
+		for node in root.named_mut("a") {
+			node.set_value("x");
+		}
+		assert_eq!(
+			root.named("a").map(BmlNode::value).collect::<Vec<_>>(),
+			vec!["x", "x"]
+		);
+		assert_eq!(root.remove("a"), 2);
+		assert_eq!(root.named("a").count(), 0);
+		assert_eq!(root.named("b").next().unwrap().value(), "3");
+	}
+
+	#[test]
+	fn nth_selects_by_index_and_is_none_out_of_range() {
+		let root = BmlNode::try_from("proxy: a\nproxy: b\nproxy: c\n").unwrap();
+		assert_eq!(root.nth("proxy", 0).unwrap().value(), "a");
+		assert_eq!(root.nth("proxy", 2).unwrap().value(), "c");
+		assert!(root.nth("proxy", 3).is_none());
+		assert!(root.nth("missing", 0).is_none());
+	}
+
+	#[test]
+	fn at_walks_an_indexed_slash_separated_path() {
+		let root =
+			BmlNode::try_from("server\n  proxy\n    host: a\n  proxy\n    host: b\n").unwrap();
+		assert_eq!(root.at("server/proxy[0]/host").unwrap().value(), "a");
+		assert_eq!(root.at("server/proxy[1]/host").unwrap().value(), "b");
+		assert_eq!(root.at("server/proxy/host").unwrap().value(), "a");
+	}
+
+	#[test]
+	fn at_is_none_for_out_of_range_or_malformed_index() {
+		let root = BmlNode::try_from("server\n  proxy\n    host: a\n").unwrap();
+		assert!(root.at("server/proxy[5]/host").is_none());
+		assert!(root.at("server/missing").is_none());
+		assert!(root.at("server/proxy[bad]/host").is_none());
+		assert!(root.at("server/proxy[0/host").is_none());
+	}
+
+	#[test]
+	fn try_from_spanned_records_byte_offsets_and_line_col() {
+		let bml = "database\n  proxy port=\"8080\"\n    host: example.com\n";
+		let root = BmlNode::try_from_spanned(bml).unwrap();
+
+		let (name, database) = root.nodes().next().unwrap();
+		assert_eq!(name, "database");
+		let span = database.span().unwrap();
+		assert_eq!(span.line, 1);
+		assert_eq!(span.col, 1);
+		assert_eq!(&bml[span.start..span.start + name.len()], "database");
+
+		let (name, proxy) = database.nodes().next().unwrap();
+		assert_eq!(name, "proxy");
+		let span = proxy.span().unwrap();
+		assert_eq!(span.line, 2);
+		assert_eq!(span.col, 3);
+		assert_eq!(&bml[span.start..span.start + name.len()], "proxy");
+
+		let (name, port) = proxy.nodes().next().unwrap();
+		assert_eq!(name, "port");
+		let span = port.span().unwrap();
+		assert_eq!(span.line, 2);
+		assert_eq!(span.col, 9);
+		assert_eq!(&bml[span.start..span.start + name.len()], "port");
+	}
+
+	#[test]
+	fn try_from_leaves_span_unset() {
+		let root = BmlNode::try_from("proxy\n").unwrap();
+		let (_name, proxy) = root.nodes().next().unwrap();
+		assert!(proxy.span().is_none());
+	}
 }
@@ -0,0 +1,726 @@
+//! Serialize a typed value into a [`BmlNode`] tree, reusing its existing [`Display`] impl to emit
+//! BML text. Mirrors `toml_edit`'s `ser` module.
+//!
+//! Struct and map fields become named child nodes, a repeated field of type `Vec<T>` becomes
+//! sibling nodes of the same name, and an enum is represented either by its scalar value (unit
+//! variants) or by the variant name selecting a single child node (newtype variants). Tuple and
+//! struct variants are not supported and return an error.
+
+use core::fmt;
+
+use serde::ser::{self, Error as _, Serialize};
+
+use crate::{BmlError, BmlName, BmlNode};
+
+/// Serializes `value` into a BML [`String`](std::string::String).
+///
+/// # Errors
+///
+/// Returns [`BmlError::Serde`] if `value` cannot be represented as a BML document, which must be
+/// a struct or map at its root.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<std::string::String, BmlError> {
+	let mut root = BmlNode::root();
+	value
+		.serialize(DocSerializer(&mut root))
+		.map_err(|Error(message)| BmlError::Serde(message))?;
+	Ok(root.to_string())
+}
+
+#[derive(Debug)]
+struct Error(std::string::String);
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+	fn custom<T: fmt::Display>(message: T) -> Self {
+		Self(message.to_string())
+	}
+}
+
+fn unsupported<T>(what: &str) -> Result<T, Error> {
+	Err(Error::custom(format!("BML cannot represent a bare {what}")))
+}
+
+/// Serializer for the document root: only a struct or map is a valid BML document.
+struct DocSerializer<'a>(&'a mut BmlNode);
+
+impl<'a> ser::Serializer for DocSerializer<'a> {
+	type Ok = ();
+	type Error = Error;
+	type SerializeSeq = ser::Impossible<(), Error>;
+	type SerializeTuple = ser::Impossible<(), Error>;
+	type SerializeTupleStruct = ser::Impossible<(), Error>;
+	type SerializeTupleVariant = ser::Impossible<(), Error>;
+	type SerializeMap = StructSerializer<'a>;
+	type SerializeStruct = StructSerializer<'a>;
+	type SerializeStructVariant = ser::Impossible<(), Error>;
+
+	fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+		Ok(StructSerializer::Root(self.0))
+	}
+
+	fn serialize_struct(
+		self,
+		_name: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeStruct, Error> {
+		Ok(StructSerializer::Root(self.0))
+	}
+
+	fn collect_str<T: fmt::Display + ?Sized>(self, _value: &T) -> Result<(), Error> {
+		unsupported("scalar")
+	}
+
+	fn serialize_bool(self, _v: bool) -> Result<(), Error> {
+		unsupported("bool")
+	}
+	fn serialize_i8(self, _v: i8) -> Result<(), Error> {
+		unsupported("integer")
+	}
+	fn serialize_i16(self, _v: i16) -> Result<(), Error> {
+		unsupported("integer")
+	}
+	fn serialize_i32(self, _v: i32) -> Result<(), Error> {
+		unsupported("integer")
+	}
+	fn serialize_i64(self, _v: i64) -> Result<(), Error> {
+		unsupported("integer")
+	}
+	fn serialize_u8(self, _v: u8) -> Result<(), Error> {
+		unsupported("integer")
+	}
+	fn serialize_u16(self, _v: u16) -> Result<(), Error> {
+		unsupported("integer")
+	}
+	fn serialize_u32(self, _v: u32) -> Result<(), Error> {
+		unsupported("integer")
+	}
+	fn serialize_u64(self, _v: u64) -> Result<(), Error> {
+		unsupported("integer")
+	}
+	fn serialize_f32(self, _v: f32) -> Result<(), Error> {
+		unsupported("float")
+	}
+	fn serialize_f64(self, _v: f64) -> Result<(), Error> {
+		unsupported("float")
+	}
+	fn serialize_char(self, _v: char) -> Result<(), Error> {
+		unsupported("char")
+	}
+	fn serialize_str(self, _v: &str) -> Result<(), Error> {
+		unsupported("string")
+	}
+	fn serialize_bytes(self, _v: &[u8]) -> Result<(), Error> {
+		unsupported("bytes")
+	}
+	fn serialize_none(self) -> Result<(), Error> {
+		unsupported("option")
+	}
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+		value.serialize(self)
+	}
+	fn serialize_unit(self) -> Result<(), Error> {
+		unsupported("unit")
+	}
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+		unsupported("unit")
+	}
+	fn serialize_unit_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+	) -> Result<(), Error> {
+		unsupported("enum")
+	}
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		value: &T,
+	) -> Result<(), Error> {
+		value.serialize(self)
+	}
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_value: &T,
+	) -> Result<(), Error> {
+		unsupported("enum")
+	}
+	fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+		unsupported("sequence")
+	}
+	fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+		unsupported("tuple")
+	}
+	fn serialize_tuple_struct(
+		self,
+		_name: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeTupleStruct, Error> {
+		unsupported("tuple")
+	}
+	fn serialize_tuple_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeTupleVariant, Error> {
+		unsupported("enum")
+	}
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeStructVariant, Error> {
+		unsupported("enum")
+	}
+}
+
+/// Builds a struct or map's fields, either directly as the document root or as a nested element
+/// appended to its parent once all fields have been collected.
+enum StructSerializer<'a> {
+	Root(&'a mut BmlNode),
+	Nested {
+		parent: &'a mut BmlNode,
+		name: BmlName,
+		node: Box<BmlNode>,
+	},
+}
+
+impl<'a> StructSerializer<'a> {
+	fn node(&mut self) -> &mut BmlNode {
+		match self {
+			Self::Root(node) => node,
+			Self::Nested { node, .. } => node,
+		}
+	}
+
+	fn finish(self) {
+		if let Self::Nested { parent, name, node } = self {
+			parent.append((name, *node));
+		}
+	}
+}
+
+impl<'a> ser::SerializeStruct for StructSerializer<'a> {
+	type Ok = ();
+	type Error = Error;
+
+	fn serialize_field<T: Serialize + ?Sized>(
+		&mut self,
+		key: &'static str,
+		value: &T,
+	) -> Result<(), Error> {
+		value.serialize(FieldSerializer {
+			parent: self.node(),
+			name: key.into(),
+		})
+	}
+
+	fn end(self) -> Result<(), Error> {
+		self.finish();
+		Ok(())
+	}
+}
+
+impl<'a> ser::SerializeMap for StructSerializer<'a> {
+	type Ok = ();
+	type Error = Error;
+
+	fn serialize_key<T: Serialize + ?Sized>(&mut self, _key: &T) -> Result<(), Error> {
+		unreachable!("serialize_entry is always used instead")
+	}
+
+	fn serialize_value<T: Serialize + ?Sized>(&mut self, _value: &T) -> Result<(), Error> {
+		unreachable!("serialize_entry is always used instead")
+	}
+
+	fn serialize_entry<K: Serialize + ?Sized, V: Serialize + ?Sized>(
+		&mut self,
+		key: &K,
+		value: &V,
+	) -> Result<(), Error> {
+		let name = key.serialize(KeySerializer)?;
+		value.serialize(FieldSerializer {
+			parent: self.node(),
+			name,
+		})
+	}
+
+	fn end(self) -> Result<(), Error> {
+		self.finish();
+		Ok(())
+	}
+}
+
+/// Serializes a map key into a [`BmlName`], the only key representation BML supports.
+struct KeySerializer;
+
+impl ser::Serializer for KeySerializer {
+	type Ok = BmlName;
+	type Error = Error;
+	type SerializeSeq = ser::Impossible<BmlName, Error>;
+	type SerializeTuple = ser::Impossible<BmlName, Error>;
+	type SerializeTupleStruct = ser::Impossible<BmlName, Error>;
+	type SerializeTupleVariant = ser::Impossible<BmlName, Error>;
+	type SerializeMap = ser::Impossible<BmlName, Error>;
+	type SerializeStruct = ser::Impossible<BmlName, Error>;
+	type SerializeStructVariant = ser::Impossible<BmlName, Error>;
+
+	fn collect_str<T: fmt::Display + ?Sized>(self, value: &T) -> Result<BmlName, Error> {
+		Ok(value.to_string().into())
+	}
+
+	fn serialize_str(self, v: &str) -> Result<BmlName, Error> {
+		Ok(v.into())
+	}
+	fn serialize_bool(self, v: bool) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_i8(self, v: i8) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_i16(self, v: i16) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_i32(self, v: i32) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_i64(self, v: i64) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_u8(self, v: u8) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_u16(self, v: u16) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_u32(self, v: u32) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_u64(self, v: u64) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_f32(self, v: f32) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_f64(self, v: f64) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_char(self, v: char) -> Result<BmlName, Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_bytes(self, _v: &[u8]) -> Result<BmlName, Error> {
+		unsupported("bytes key")
+	}
+	fn serialize_none(self) -> Result<BmlName, Error> {
+		unsupported("option key")
+	}
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<BmlName, Error> {
+		value.serialize(self)
+	}
+	fn serialize_unit(self) -> Result<BmlName, Error> {
+		unsupported("unit key")
+	}
+	fn serialize_unit_struct(self, name: &'static str) -> Result<BmlName, Error> {
+		Ok(name.into())
+	}
+	fn serialize_unit_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+	) -> Result<BmlName, Error> {
+		Ok(variant.into())
+	}
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		value: &T,
+	) -> Result<BmlName, Error> {
+		value.serialize(self)
+	}
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_value: &T,
+	) -> Result<BmlName, Error> {
+		unsupported("enum key")
+	}
+	fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+		unsupported("sequence key")
+	}
+	fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+		unsupported("tuple key")
+	}
+	fn serialize_tuple_struct(
+		self,
+		_name: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeTupleStruct, Error> {
+		unsupported("tuple key")
+	}
+	fn serialize_tuple_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeTupleVariant, Error> {
+		unsupported("enum key")
+	}
+	fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+		unsupported("map key")
+	}
+	fn serialize_struct(
+		self,
+		_name: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeStruct, Error> {
+		unsupported("struct key")
+	}
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeStructVariant, Error> {
+		unsupported("enum key")
+	}
+}
+
+/// Serializes one value into `parent` under `name`, appending one or more sibling nodes.
+struct FieldSerializer<'a> {
+	parent: &'a mut BmlNode,
+	name: BmlName,
+}
+
+impl<'a> FieldSerializer<'a> {
+	fn push_scalar(self, data: std::string::String) {
+		let mut elem = BmlNode::elem();
+		elem.data.push_str(&data);
+		elem.data.push('\n');
+		self.parent.append((self.name, elem));
+	}
+
+	/// Wraps `value` in a variant container so [`crate::de`] can read the node name back as the
+	/// selected variant.
+	fn push_variant<T: Serialize + ?Sized>(
+		self,
+		variant: &'static str,
+		value: &T,
+	) -> Result<(), Error> {
+		let mut container = BmlNode::elem();
+		value.serialize(FieldSerializer {
+			parent: &mut container,
+			name: variant.into(),
+		})?;
+		self.parent.append((self.name, container));
+		Ok(())
+	}
+}
+
+impl<'a> ser::Serializer for FieldSerializer<'a> {
+	type Ok = ();
+	type Error = Error;
+	type SerializeSeq = SeqSerializer<'a>;
+	type SerializeTuple = SeqSerializer<'a>;
+	type SerializeTupleStruct = SeqSerializer<'a>;
+	type SerializeTupleVariant = ser::Impossible<(), Error>;
+	type SerializeMap = StructSerializer<'a>;
+	type SerializeStruct = StructSerializer<'a>;
+	type SerializeStructVariant = ser::Impossible<(), Error>;
+
+	fn collect_str<T: fmt::Display + ?Sized>(self, value: &T) -> Result<(), Error> {
+		self.push_scalar(value.to_string());
+		Ok(())
+	}
+
+	fn serialize_bool(self, v: bool) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_i8(self, v: i8) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_i16(self, v: i16) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_i32(self, v: i32) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_i64(self, v: i64) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_u8(self, v: u8) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_u16(self, v: u16) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_u32(self, v: u32) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_u64(self, v: u64) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_f32(self, v: f32) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_f64(self, v: f64) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_char(self, v: char) -> Result<(), Error> {
+		self.collect_str(&v)
+	}
+	fn serialize_str(self, v: &str) -> Result<(), Error> {
+		self.push_scalar(v.into());
+		Ok(())
+	}
+	fn serialize_bytes(self, _v: &[u8]) -> Result<(), Error> {
+		unsupported("bytes")
+	}
+	fn serialize_none(self) -> Result<(), Error> {
+		// A missing `Option` field simply does not get a node.
+		Ok(())
+	}
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+		value.serialize(self)
+	}
+	fn serialize_unit(self) -> Result<(), Error> {
+		self.parent.append((self.name, BmlNode::elem()));
+		Ok(())
+	}
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+		self.serialize_unit()
+	}
+	fn serialize_unit_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+	) -> Result<(), Error> {
+		self.push_scalar(variant.into());
+		Ok(())
+	}
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		value: &T,
+	) -> Result<(), Error> {
+		value.serialize(self)
+	}
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(
+		self,
+		_name: &'static str,
+		_index: u32,
+		variant: &'static str,
+		value: &T,
+	) -> Result<(), Error> {
+		self.push_variant(variant, value)
+	}
+	fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+		Ok(SeqSerializer {
+			parent: self.parent,
+			name: self.name,
+		})
+	}
+	fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+		self.serialize_seq(Some(len))
+	}
+	fn serialize_tuple_struct(
+		self,
+		_name: &'static str,
+		len: usize,
+	) -> Result<Self::SerializeTupleStruct, Error> {
+		self.serialize_seq(Some(len))
+	}
+	fn serialize_tuple_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeTupleVariant, Error> {
+		unsupported("tuple enum variant")
+	}
+	fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+		Ok(StructSerializer::Nested {
+			parent: self.parent,
+			name: self.name,
+			node: Box::new(BmlNode::elem()),
+		})
+	}
+	fn serialize_struct(
+		self,
+		_name: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeStruct, Error> {
+		Ok(StructSerializer::Nested {
+			parent: self.parent,
+			name: self.name,
+			node: Box::new(BmlNode::elem()),
+		})
+	}
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self::SerializeStructVariant, Error> {
+		unsupported("struct enum variant")
+	}
+}
+
+/// Appends each sequence element as a sibling node sharing the field's name.
+struct SeqSerializer<'a> {
+	parent: &'a mut BmlNode,
+	name: BmlName,
+}
+
+impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
+	type Ok = ();
+	type Error = Error;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		value.serialize(FieldSerializer {
+			parent: self.parent,
+			name: self.name.clone(),
+		})
+	}
+
+	fn end(self) -> Result<(), Error> {
+		Ok(())
+	}
+}
+
+impl<'a> ser::SerializeTuple for SeqSerializer<'a> {
+	type Ok = ();
+	type Error = Error;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<(), Error> {
+		Ok(())
+	}
+}
+
+impl<'a> ser::SerializeTupleStruct for SeqSerializer<'a> {
+	type Ok = ();
+	type Error = Error;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<(), Error> {
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde::{Deserialize, Serialize};
+
+	use super::to_string;
+	use crate::de::from_str;
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct Proxy {
+		host: std::string::String,
+	}
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	#[serde(rename_all = "lowercase")]
+	enum Authentication {
+		Plain,
+		None,
+	}
+
+	#[test]
+	fn round_trips_scalars_and_description_lines() {
+		#[derive(Debug, PartialEq, Serialize, Deserialize)]
+		struct Server {
+			port: u16,
+			service: bool,
+			host: std::string::String,
+			description: std::vec::Vec<std::string::String>,
+		}
+		let server = Server {
+			port: 80,
+			service: true,
+			host: "example.com".into(),
+			description: std::vec![
+				"Primary web-facing server".into(),
+				"Provides commerce-related functionality".into(),
+			],
+		};
+		let bml = to_string(&server).unwrap();
+		assert_eq!(from_str::<Server>(&bml).unwrap(), server);
+	}
+
+	#[test]
+	fn round_trips_a_single_occurrence_of_a_repeated_struct() {
+		#[derive(Debug, PartialEq, Serialize, Deserialize)]
+		struct Server {
+			proxy: std::vec::Vec<Proxy>,
+		}
+		let server = Server {
+			proxy: std::vec![Proxy {
+				host: "proxy.example.com".into(),
+			}],
+		};
+		let bml = to_string(&server).unwrap();
+		assert_eq!(from_str::<Server>(&bml).unwrap(), server);
+	}
+
+	#[test]
+	fn round_trips_multiple_occurrences_of_a_repeated_struct() {
+		#[derive(Debug, PartialEq, Serialize, Deserialize)]
+		struct Server {
+			proxy: std::vec::Vec<Proxy>,
+		}
+		let server = Server {
+			proxy: std::vec![
+				Proxy {
+					host: "a.example.com".into(),
+				},
+				Proxy {
+					host: "b.example.com".into(),
+				},
+			],
+		};
+		let bml = to_string(&server).unwrap();
+		assert_eq!(from_str::<Server>(&bml).unwrap(), server);
+	}
+
+	#[test]
+	fn round_trips_content_based_enum_variant() {
+		#[derive(Debug, PartialEq, Serialize, Deserialize)]
+		struct Proxy {
+			authentication: Authentication,
+		}
+		let proxy = Proxy {
+			authentication: Authentication::Plain,
+		};
+		let bml = to_string(&proxy).unwrap();
+		assert_eq!(from_str::<Proxy>(&bml).unwrap(), proxy);
+	}
+}
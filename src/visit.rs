@@ -0,0 +1,217 @@
+//! Read-only and mutating tree traversal, mirroring `toml_edit`'s `examples/visit.rs` and
+//! `dhall_syntax`'s `visitor.rs`.
+//!
+//! Both [`Visit`] and [`VisitMut`] are default-implemented in terms of a `visit_children` walker,
+//! so a concrete visitor overrides only the methods it cares about, e.g. renaming every node of a
+//! given name, collecting all values under a path, or stripping attributes, without having to
+//! hand-roll the recursion through [`BmlNode::nodes()`] or the private `ListOrderedMultimap`.
+
+use ordered_multimap::ListOrderedMultimap;
+
+use crate::{BmlKind, BmlName, BmlNode};
+
+/// Read-only visitor over a [`BmlNode`] tree.
+pub trait Visit {
+	/// Visits a child element node named `name`.
+	fn visit_node(&mut self, name: &str, node: &BmlNode) {
+		let _ = name;
+		visit_children(self, node);
+	}
+	/// Visits a child attribute node named `name`.
+	fn visit_attr(&mut self, name: &str, attr: &BmlNode) {
+		let _ = name;
+		visit_children(self, attr);
+	}
+	/// Visits a data line of the node currently being recursed into.
+	fn visit_data(&mut self, line: &str) {
+		let _ = line;
+	}
+}
+
+/// Visits `root`'s data lines and child nodes with `visitor`.
+pub fn visit_bml<V: Visit + ?Sized>(root: &BmlNode, visitor: &mut V) {
+	visit_children(visitor, root);
+}
+
+/// Recurses into `node`'s data lines and child nodes, dispatching each child to
+/// [`Visit::visit_attr`] or [`Visit::visit_node`] depending on its kind.
+pub fn visit_children<V: Visit + ?Sized>(visitor: &mut V, node: &BmlNode) {
+	for line in node.lines() {
+		visitor.visit_data(line);
+	}
+	for (name, child) in node.nodes() {
+		if matches!(child.kind, BmlKind::Attr { .. }) {
+			visitor.visit_attr(name, child);
+		} else {
+			visitor.visit_node(name, child);
+		}
+	}
+}
+
+/// Mutating visitor over a [`BmlNode`] tree.
+///
+/// Each method owns the child's `name` and returns it wrapped in `Some` to keep the child under
+/// that (possibly changed) name, or `None` to drop it — the only way to rename or selectively
+/// remove a child, since the parent's `ListOrderedMultimap` is private to the crate.
+pub trait VisitMut {
+	/// Visits a child element node named `name`.
+	fn visit_node_mut(
+		&mut self,
+		name: std::string::String,
+		node: &mut BmlNode,
+	) -> Option<std::string::String> {
+		visit_children_mut(self, node);
+		Some(name)
+	}
+	/// Visits a child attribute node named `name`.
+	fn visit_attr_mut(
+		&mut self,
+		name: std::string::String,
+		attr: &mut BmlNode,
+	) -> Option<std::string::String> {
+		visit_children_mut(self, attr);
+		Some(name)
+	}
+}
+
+/// Visits `root`'s child nodes with `visitor`.
+pub fn visit_bml_mut<V: VisitMut + ?Sized>(root: &mut BmlNode, visitor: &mut V) {
+	visit_children_mut(visitor, root);
+}
+
+/// Recurses into `node`'s child nodes, dispatching each to [`VisitMut::visit_attr_mut`] or
+/// [`VisitMut::visit_node_mut`] depending on its kind, and rebuilding `node`'s children from
+/// whatever (name, kept?) each call returns.
+///
+/// Names cross the boundary as plain [`String`](std::string::String) rather than the crate's
+/// internal [`BmlName`], which downstream visitors have no way to name.
+pub fn visit_children_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut BmlNode) {
+	let mut retained = ListOrderedMultimap::default();
+	for (name, mut child) in core::mem::take(&mut node.node) {
+		let name = std::string::String::from(&*name);
+		let name = if matches!(child.kind, BmlKind::Attr { .. }) {
+			visitor.visit_attr_mut(name, &mut child)
+		} else {
+			visitor.visit_node_mut(name, &mut child)
+		};
+		if let Some(name) = name {
+			retained.append(BmlName::from(name.as_str()), child);
+		}
+	}
+	node.node = retained;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{visit_bml, visit_bml_mut, visit_children_mut, Visit, VisitMut};
+	use crate::BmlNode;
+
+	struct NodeNames(std::vec::Vec<std::string::String>);
+
+	impl Visit for NodeNames {
+		fn visit_node(&mut self, name: &str, node: &BmlNode) {
+			self.0.push(name.into());
+			visit_bml(node, self);
+		}
+	}
+
+	#[test]
+	fn collects_node_names() {
+		let root = BmlNode::try_from("server\n  proxy\n    host: a\n").unwrap();
+		let mut names = NodeNames(std::vec::Vec::new());
+		visit_bml(&root, &mut names);
+		assert_eq!(names.0, std::vec!["server", "proxy", "host"]);
+	}
+
+	struct Values(std::vec::Vec<std::string::String>);
+
+	impl Visit for Values {
+		fn visit_data(&mut self, line: &str) {
+			self.0.push(line.into());
+		}
+		fn visit_node(&mut self, _name: &str, node: &BmlNode) {
+			visit_bml(node, self);
+		}
+	}
+
+	#[test]
+	fn collects_all_values() {
+		let root = BmlNode::try_from("host: example.com\nport: 80\n").unwrap();
+		let mut values = Values(std::vec::Vec::new());
+		visit_bml(&root, &mut values);
+		assert_eq!(values.0, std::vec!["example.com", "80"]);
+	}
+
+	struct StripAttrs;
+
+	impl VisitMut for StripAttrs {
+		fn visit_attr_mut(
+			&mut self,
+			_name: std::string::String,
+			_attr: &mut BmlNode,
+		) -> Option<std::string::String> {
+			None
+		}
+	}
+
+	#[test]
+	fn strips_attributes() {
+		let mut root =
+			BmlNode::try_from("proxy host=\"a\" port=\"1\"\n  authentication: plain\n").unwrap();
+		visit_bml_mut(&mut root, &mut StripAttrs);
+		let (name, proxy) = root.nodes().next().unwrap();
+		assert_eq!(name, "proxy");
+		assert_eq!(proxy.nodes().count(), 1);
+		assert_eq!(
+			proxy.named("authentication").next().unwrap().value(),
+			"plain"
+		);
+	}
+
+	#[test]
+	fn strips_attributes_leaving_a_same_named_element_sibling_intact() {
+		// `host` appears both as an attribute and, once stripped, should still leave the
+		// same-named element child alone: attributes and elements are distinguished by kind,
+		// not name, and `remove()` would have taken both out indiscriminately.
+		let mut root = BmlNode::try_from("proxy host=\"a\"\n  host: example.com\n").unwrap();
+		visit_bml_mut(&mut root, &mut StripAttrs);
+		let (name, proxy) = root.nodes().next().unwrap();
+		assert_eq!(name, "proxy");
+		assert_eq!(proxy.nodes().count(), 1);
+		assert_eq!(proxy.named("host").next().unwrap().value(), "example.com");
+	}
+
+	struct Rename<'a> {
+		from: &'a str,
+		to: &'a str,
+	}
+
+	impl VisitMut for Rename<'_> {
+		fn visit_node_mut(
+			&mut self,
+			name: std::string::String,
+			node: &mut BmlNode,
+		) -> Option<std::string::String> {
+			visit_children_mut(self, node);
+			Some(if name == self.from {
+				self.to.into()
+			} else {
+				name
+			})
+		}
+	}
+
+	#[test]
+	fn renames_every_node_of_a_given_name() {
+		let mut root = BmlNode::try_from("proxy\n  host: a\nproxy\n  host: b\n").unwrap();
+		visit_bml_mut(
+			&mut root,
+			&mut Rename {
+				from: "proxy",
+				to: "server",
+			},
+		);
+		assert_eq!(root.named("proxy").count(), 0);
+		assert_eq!(root.named("server").count(), 2);
+	}
+}